@@ -0,0 +1,418 @@
+use crate::{shell::element::CosmicMapped, state::State, utils::prelude::*};
+
+use indexmap::IndexSet;
+use smithay::{
+    backend::renderer::{element::AsRenderElements, ImportAll, Renderer},
+    desktop::space::SpaceElement,
+    input::{pointer::GrabStartData as PointerGrabStartData, Seat},
+    output::Output,
+    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::ResizeEdge,
+    render_elements,
+    utils::{IsAlive, Logical, Point, Rectangle, Scale, Serial},
+};
+use std::collections::HashMap;
+
+use super::super::grabs::ResizeGrab;
+
+/// Columns never shrink narrower than this, regardless of how far a
+/// resize keybinding is repeated.
+const MIN_COLUMN_WIDTH: i32 = 100;
+
+/// A column of windows stacked vertically, occupying the full output height
+/// between them. `weights` gives the fraction of the column's height each
+/// window in `windows` receives (same length and order as `windows`); an
+/// empty `weights` means split evenly. `width` is this column's own width in
+/// logical pixels, resized independently of every other column on the
+/// output by `resize_active_column_width`.
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub windows: IndexSet<CosmicMapped>,
+    pub weights: Vec<f64>,
+    pub width: i32,
+}
+
+impl Column {
+    fn new(window: CosmicMapped, width: i32) -> Column {
+        let mut windows = IndexSet::new();
+        windows.insert(window);
+        Column {
+            windows,
+            weights: Vec::new(),
+            width,
+        }
+    }
+
+    fn height_for(&self, idx: usize, total_height: i32) -> i32 {
+        if self.weights.len() != self.windows.len() || self.weights.is_empty() {
+            total_height / self.windows.len().max(1) as i32
+        } else {
+            let sum: f64 = self.weights.iter().sum();
+            ((self.weights[idx] / sum) * total_height as f64).round() as i32
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OutputData {
+    position: Point<i32, Logical>,
+    working_area: Rectangle<i32, Logical>,
+    columns: Vec<Column>,
+    active_column: usize,
+    /// Index into the active column's `windows` that currently holds
+    /// keyboard focus; kept in range by `refresh`, and reset to 0 whenever
+    /// focus moves to another column or a new column is mapped.
+    active_row: usize,
+    viewport_offset: i32,
+    /// Width newly-mapped columns are created with. Existing columns keep
+    /// their own width afterward (see `Column::width`), resized
+    /// independently of this and of each other.
+    default_column_width: i32,
+}
+
+#[derive(Debug, Default)]
+pub struct ScrollingLayout {
+    outputs: HashMap<Output, OutputData>,
+}
+
+impl ScrollingLayout {
+    pub fn new() -> ScrollingLayout {
+        ScrollingLayout::default()
+    }
+
+    pub fn map_output(&mut self, output: &Output, position: Point<i32, Logical>) {
+        self.outputs.entry(output.clone()).or_insert_with(|| {
+            let working_area = Rectangle::from_loc_and_size((0, 0), output.geometry().size);
+            let width = working_area.size.w.max(1);
+            OutputData {
+                position,
+                working_area,
+                columns: Vec::new(),
+                active_column: 0,
+                active_row: 0,
+                viewport_offset: 0,
+                default_column_width: width / 2,
+            }
+        });
+    }
+
+    pub fn unmap_output(&mut self, output: &Output) {
+        self.outputs.remove(output);
+    }
+
+    /// Updates the area columns are laid out within (the output geometry
+    /// minus any layer-shell exclusive zones), keeping every column's width
+    /// proportional to the new working width and re-clamping the viewport
+    /// so a column that was scrolled to the old edge doesn't end up
+    /// scrolled past the new one.
+    pub fn set_working_area(&mut self, output: &Output, area: Rectangle<i32, Logical>) {
+        if let Some(data) = self.outputs.get_mut(output) {
+            let old_width = data.working_area.size.w.max(1);
+            let new_width = area.size.w.max(1);
+            data.default_column_width = (data.default_column_width * new_width) / old_width;
+            for column in &mut data.columns {
+                column.width = ((column.width * new_width) / old_width).max(MIN_COLUMN_WIDTH);
+            }
+            data.working_area = area;
+            Self::clamp_viewport_offset(data);
+        }
+    }
+
+    /// Total width of every column on an output, the extent the viewport
+    /// can be scrolled across.
+    fn total_columns_width(columns: &[Column]) -> i32 {
+        columns.iter().map(|c| c.width).sum()
+    }
+
+    /// Logical-pixel x offset of the `idx`th column from the first column,
+    /// before `position`/`working_area.loc`/`viewport_offset` are applied.
+    fn column_x_offset(columns: &[Column], idx: usize) -> i32 {
+        columns[..idx].iter().map(|c| c.width).sum()
+    }
+
+    /// Clamps `viewport_offset` back into `[0, max_offset]` for the
+    /// output's current columns and working area, without otherwise
+    /// changing which column is scrolled into view. Needed anywhere
+    /// columns or the working area change outside of a focus-driven
+    /// `scroll_to_active` call (`set_working_area`, `unmap`, `refresh`),
+    /// since removing columns or shrinking the working area can leave a
+    /// previously-valid offset scrolled past the new maximum, silently
+    /// pushing the active column out of `render_output`'s overlap test
+    /// until the next focus move happens to call `scroll_to_active`.
+    fn clamp_viewport_offset(data: &mut OutputData) {
+        let output_width = data.working_area.size.w;
+        let max_offset = (Self::total_columns_width(&data.columns) - output_width).max(0);
+        data.viewport_offset = data.viewport_offset.clamp(0, max_offset);
+    }
+
+    pub fn refresh(&mut self) {
+        for data in self.outputs.values_mut() {
+            for column in &mut data.columns {
+                column.windows.retain(|w| w.alive());
+            }
+            data.columns.retain(|c| !c.windows.is_empty());
+            if data.active_column >= data.columns.len() {
+                data.active_column = data.columns.len().saturating_sub(1);
+            }
+            if let Some(column) = data.columns.get(data.active_column) {
+                data.active_row = data.active_row.min(column.windows.len().saturating_sub(1));
+            } else {
+                data.active_row = 0;
+            }
+            Self::clamp_viewport_offset(data);
+        }
+    }
+
+    fn active_output_mut(&mut self, seat: &Seat<State>) -> Option<(&Output, &mut OutputData)> {
+        let output = seat.active_output();
+        self.outputs
+            .iter_mut()
+            .find(|(o, _)| **o == output)
+            .map(|(o, d)| (o, d))
+    }
+
+    pub fn map(&mut self, window: CosmicMapped, seat: &Seat<State>) {
+        let Some((_, data)) = self.active_output_mut(seat) else {
+            return;
+        };
+        let insert_at = if data.columns.is_empty() {
+            0
+        } else {
+            data.active_column + 1
+        };
+        data.columns
+            .insert(insert_at, Column::new(window, data.default_column_width));
+        data.active_column = insert_at;
+        data.active_row = 0;
+        self.scroll_to_active(seat);
+    }
+
+    pub fn unmap(&mut self, mapped: &CosmicMapped) -> bool {
+        let mut found = false;
+        for data in self.outputs.values_mut() {
+            for column in &mut data.columns {
+                if column.windows.shift_remove(mapped) {
+                    found = true;
+                }
+            }
+            data.columns.retain(|c| !c.windows.is_empty());
+            if data.active_column >= data.columns.len() {
+                data.active_column = data.columns.len().saturating_sub(1);
+            }
+            Self::clamp_viewport_offset(data);
+        }
+        found
+    }
+
+    pub fn mapped(&self) -> impl Iterator<Item = (&Output, &CosmicMapped, Point<i32, Logical>)> {
+        self.outputs.iter().flat_map(|(output, data)| {
+            let height = data.working_area.size.h;
+            data.columns.iter().enumerate().flat_map(move |(col_idx, column)| {
+                let x = data.position.x + data.working_area.loc.x
+                    + Self::column_x_offset(&data.columns, col_idx)
+                    - data.viewport_offset;
+                let mut y = data.position.y + data.working_area.loc.y;
+                column
+                    .windows
+                    .iter()
+                    .enumerate()
+                    .map(move |(win_idx, mapped)| {
+                        let h = column.height_for(win_idx, height);
+                        let loc = (x, y);
+                        y += h;
+                        (output, mapped, loc.into())
+                    })
+            })
+        })
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = (&Output, smithay::desktop::Window, Point<i32, Logical>)> {
+        self.mapped()
+            .flat_map(|(output, mapped, loc)| mapped.windows().map(move |(w, _)| (output, w, loc)))
+    }
+
+    pub fn output_for_element(&self, elem: &CosmicMapped) -> Option<&Output> {
+        self.outputs
+            .iter()
+            .find(|(_, data)| data.columns.iter().any(|c| c.windows.contains(elem)))
+            .map(|(o, _)| o)
+    }
+
+    pub fn element_geometry(&self, elem: &CosmicMapped) -> Option<Rectangle<i32, Logical>> {
+        self.mapped()
+            .find(|(_, mapped, _)| *mapped == elem)
+            .map(|(_, mapped, loc)| Rectangle::from_loc_and_size(loc, mapped.geometry().size))
+    }
+
+    /// Scrolls the viewport so the active column is fully visible, clamping
+    /// so that the first/last column snaps to the output edge instead of
+    /// leaving a gap.
+    fn scroll_to_active(&mut self, seat: &Seat<State>) {
+        let Some((_, data)) = self.active_output_mut(seat) else {
+            return;
+        };
+        if data.columns.is_empty() {
+            return;
+        }
+        let output_width = data.working_area.size.w;
+        let active_width = data.columns[data.active_column].width;
+        let active_x = Self::column_x_offset(&data.columns, data.active_column);
+
+        if active_x < data.viewport_offset {
+            data.viewport_offset = active_x;
+        } else if active_x + active_width > data.viewport_offset + output_width {
+            data.viewport_offset = active_x + active_width - output_width;
+        }
+        Self::clamp_viewport_offset(data);
+    }
+
+    /// Moves focus to the column left of the active one, scrolling the
+    /// viewport so it becomes fully visible, and returns the window that is
+    /// now focused (the top window of that column).
+    pub fn move_focus_left(&mut self, seat: &Seat<State>) -> Option<CosmicMapped> {
+        if let Some((_, data)) = self.active_output_mut(seat) {
+            data.active_column = data.active_column.saturating_sub(1);
+            data.active_row = 0;
+        }
+        self.scroll_to_active(seat);
+        self.focused_window(seat)
+    }
+
+    /// Moves focus to the column right of the active one, scrolling the
+    /// viewport so it becomes fully visible, and returns the window that is
+    /// now focused (the top window of that column).
+    pub fn move_focus_right(&mut self, seat: &Seat<State>) -> Option<CosmicMapped> {
+        if let Some((_, data)) = self.active_output_mut(seat) {
+            if data.active_column + 1 < data.columns.len() {
+                data.active_column += 1;
+            }
+            data.active_row = 0;
+        }
+        self.scroll_to_active(seat);
+        self.focused_window(seat)
+    }
+
+    /// Moves focus to the window stacked above the focused one within the
+    /// active column and returns it.
+    pub fn move_focus_up(&mut self, seat: &Seat<State>) -> Option<CosmicMapped> {
+        let (_, data) = self.active_output_mut(seat)?;
+        let len = data.columns.get(data.active_column)?.windows.len();
+        if len == 0 {
+            return None;
+        }
+        data.active_row = data.active_row.min(len - 1).saturating_sub(1);
+        self.focused_window(seat)
+    }
+
+    /// Moves focus to the window stacked below the focused one within the
+    /// active column and returns it.
+    pub fn move_focus_down(&mut self, seat: &Seat<State>) -> Option<CosmicMapped> {
+        let (_, data) = self.active_output_mut(seat)?;
+        let len = data.columns.get(data.active_column)?.windows.len();
+        if len == 0 {
+            return None;
+        }
+        data.active_row = (data.active_row.min(len - 1) + 1).min(len - 1);
+        self.focused_window(seat)
+    }
+
+    /// The window currently holding the remembered focus position (active
+    /// column, active row) on `seat`'s output.
+    pub fn focused_window(&self, seat: &Seat<State>) -> Option<CosmicMapped> {
+        let output = seat.active_output();
+        let data = self.outputs.iter().find(|(o, _)| **o == output).map(|(_, d)| d)?;
+        data.columns
+            .get(data.active_column)?
+            .windows
+            .get_index(data.active_row)
+            .cloned()
+    }
+
+    /// Intentionally unimplemented: the scrolling layout has no
+    /// interactive pointer-grab resize of its own. Column width and
+    /// per-window weight are adjusted via `resize_active_column_width`
+    /// and `resize_active_window_weight` instead, which a resize
+    /// keybinding can drive directly without a grab.
+    pub fn resize_request(
+        &mut self,
+        _mapped: &CosmicMapped,
+        _seat: &Seat<State>,
+        _serial: Serial,
+        _start_data: PointerGrabStartData<State>,
+        _edges: ResizeEdge,
+    ) -> Option<ResizeGrab> {
+        None
+    }
+
+    /// Grows or shrinks the active column's width by `delta` logical
+    /// pixels, clamped to `MIN_COLUMN_WIDTH`. Only the focused column is
+    /// affected; every other column on the output keeps its own width.
+    pub fn resize_active_column_width(&mut self, seat: &Seat<State>, delta: i32) {
+        if let Some((_, data)) = self.active_output_mut(seat) {
+            let active_column = data.active_column;
+            if let Some(column) = data.columns.get_mut(active_column) {
+                column.width = (column.width + delta).max(MIN_COLUMN_WIDTH);
+            }
+        }
+        self.scroll_to_active(seat);
+    }
+
+    /// Grows the focused window's share of the active column's height by
+    /// `delta`, shrinking every other window in the column
+    /// proportionally. `weights` is initialized to an even split the
+    /// first time a column's windows are resized this way.
+    pub fn resize_active_window_weight(&mut self, seat: &Seat<State>, delta: f64) {
+        let Some((_, data)) = self.active_output_mut(seat) else {
+            return;
+        };
+        let Some(column) = data.columns.get_mut(data.active_column) else {
+            return;
+        };
+        if column.weights.len() != column.windows.len() {
+            column.weights = vec![1.0; column.windows.len()];
+        }
+        if let Some(weight) = column.weights.get_mut(data.active_row) {
+            *weight = (*weight + delta).max(0.1);
+        }
+    }
+
+    pub fn render_output<R>(
+        &self,
+        output: &Output,
+    ) -> Result<Vec<ScrollingRenderElement<R>>, crate::shell::workspace::OutputNotMapped>
+    where
+        R: Renderer + ImportAll,
+        <R as Renderer>::TextureId: 'static,
+    {
+        if !self.outputs.contains_key(output) {
+            return Err(crate::shell::workspace::OutputNotMapped);
+        }
+
+        let output_geo = output.geometry();
+        let mut render_elements = Vec::new();
+        let output_scale = output.current_scale().fractional_scale();
+
+        for (_, mapped, loc) in self.mapped().filter(|(o, _, _)| *o == output) {
+            let bbox = Rectangle::from_loc_and_size(loc, mapped.geometry().size);
+            if !bbox.overlaps(Rectangle::from_loc_and_size(output_geo.loc, output_geo.size)) {
+                // Column scrolled fully outside the output; skip it.
+                continue;
+            }
+
+            let local_loc = loc - output_geo.loc;
+            render_elements.extend(AsRenderElements::<R>::render_elements::<
+                ScrollingRenderElement<R>,
+            >(
+                mapped,
+                local_loc.to_physical_precise_round(output_scale),
+                Scale::from(output_scale),
+            ));
+        }
+
+        Ok(render_elements)
+    }
+}
+
+render_elements! {
+    pub ScrollingRenderElement<R> where R: ImportAll;
+    Window=smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement,
+}
@@ -0,0 +1,275 @@
+//! A simple grid-based auto-tiling layout: windows are arranged into rows,
+//! each row split evenly across the output height and each window within a
+//! row split evenly across the output width. This is the sole
+//! implementation backing `Workspace::tiling_layer` (confirmed by grepping
+//! the tree for other `TilingLayout`/tiling-tree candidates) — there is no
+//! separate tree-based engine elsewhere that this supersedes or duplicates.
+//!
+//! Note this is a flat row/column grid, not a tree of split nodes: there is
+//! no concept of nesting a sub-split inside a cell. If a future request
+//! needs arbitrary nested splits (as opposed to swapping/moving within the
+//! grid, which `swap_window`/`move_element` already support), that's a
+//! bigger change than this module makes today.
+
+use crate::{shell::element::CosmicMapped, shell::workspace::TilingDirection, state::State, utils::prelude::*};
+
+use smithay::{
+    backend::renderer::{element::AsRenderElements, ImportAll, Renderer},
+    input::{pointer::GrabStartData as PointerGrabStartData, Seat},
+    output::Output,
+    reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::ResizeEdge,
+    render_elements,
+    utils::{IsAlive, Logical, Point, Rectangle, Scale, Serial},
+};
+use std::collections::HashMap;
+
+use super::super::grabs::ResizeGrab;
+
+#[derive(Debug)]
+struct OutputData {
+    position: Point<i32, Logical>,
+    working_area: Rectangle<i32, Logical>,
+    /// Rows of windows. Each row is split evenly across the working
+    /// height, and each window within a row is split evenly across the
+    /// working width.
+    rows: Vec<Vec<CosmicMapped>>,
+}
+
+#[derive(Debug, Default)]
+pub struct TilingLayout {
+    outputs: HashMap<Output, OutputData>,
+}
+
+impl TilingLayout {
+    pub fn new() -> TilingLayout {
+        TilingLayout::default()
+    }
+
+    pub fn map_output(&mut self, output: &Output, position: Point<i32, Logical>) {
+        self.outputs.entry(output.clone()).or_insert_with(|| OutputData {
+            position,
+            working_area: Rectangle::from_loc_and_size((0, 0), output.geometry().size),
+            rows: Vec::new(),
+        });
+    }
+
+    pub fn unmap_output(&mut self, output: &Output) {
+        self.outputs.remove(output);
+    }
+
+    pub fn set_working_area(&mut self, output: &Output, area: Rectangle<i32, Logical>) {
+        if let Some(data) = self.outputs.get_mut(output) {
+            data.working_area = area;
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        for data in self.outputs.values_mut() {
+            for row in &mut data.rows {
+                row.retain(|w| w.alive());
+            }
+            data.rows.retain(|row| !row.is_empty());
+        }
+    }
+
+    fn active_output_mut(&mut self, seat: &Seat<State>) -> Option<(&Output, &mut OutputData)> {
+        let output = seat.active_output();
+        self.outputs
+            .iter_mut()
+            .find(|(o, _)| **o == output)
+            .map(|(o, d)| (o, d))
+    }
+
+    /// Maps a new window into the tiling grid as its own row, placed right
+    /// after the row containing the seat's currently focused window (or as
+    /// a new last row if nothing is focused yet).
+    pub fn map<'a>(
+        &mut self,
+        window: CosmicMapped,
+        seat: &Seat<State>,
+        mut focus_stack: impl Iterator<Item = &'a CosmicMapped>,
+    ) {
+        let Some((_, data)) = self.active_output_mut(seat) else {
+            return;
+        };
+        let focused = focus_stack.next();
+        let insert_at = focused
+            .and_then(|focused| data.rows.iter().position(|row| row.contains(focused)))
+            .map(|idx| idx + 1)
+            .unwrap_or(data.rows.len());
+        data.rows.insert(insert_at, vec![window]);
+    }
+
+    pub fn unmap(&mut self, mapped: &CosmicMapped) -> Option<()> {
+        let mut found = false;
+        for data in self.outputs.values_mut() {
+            for row in &mut data.rows {
+                if let Some(pos) = row.iter().position(|w| w == mapped) {
+                    row.remove(pos);
+                    found = true;
+                }
+            }
+            data.rows.retain(|row| !row.is_empty());
+        }
+        found.then_some(())
+    }
+
+    pub fn mapped(&self) -> impl Iterator<Item = (&Output, &CosmicMapped, Point<i32, Logical>)> {
+        self.outputs.iter().flat_map(|(output, data)| {
+            let row_count = data.rows.len().max(1) as i32;
+            let row_height = data.working_area.size.h / row_count;
+            data.rows.iter().enumerate().flat_map(move |(row_idx, row)| {
+                let col_count = row.len().max(1) as i32;
+                let col_width = data.working_area.size.w / col_count;
+                let y = data.position.y + data.working_area.loc.y + row_idx as i32 * row_height;
+                row.iter().enumerate().map(move |(col_idx, mapped)| {
+                    let x = data.position.x + data.working_area.loc.x + col_idx as i32 * col_width;
+                    (output, mapped, Point::from((x, y)))
+                })
+            })
+        })
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = (&Output, smithay::desktop::Window, Point<i32, Logical>)> {
+        self.mapped()
+            .flat_map(|(output, mapped, loc)| mapped.windows().map(move |(w, _)| (output, w, loc)))
+    }
+
+    pub fn output_for_element(&self, elem: &CosmicMapped) -> Option<&Output> {
+        self.outputs
+            .iter()
+            .find(|(_, data)| data.rows.iter().any(|row| row.contains(elem)))
+            .map(|(o, _)| o)
+    }
+
+    pub fn element_geometry(&self, elem: &CosmicMapped) -> Option<Rectangle<i32, Logical>> {
+        self.mapped()
+            .find(|(_, mapped, _)| *mapped == elem)
+            .map(|(_, mapped, loc)| Rectangle::from_loc_and_size(loc, mapped.geometry().size))
+    }
+
+    fn position_of(&self, elem: &CosmicMapped) -> Option<(Output, usize, usize)> {
+        self.outputs.iter().find_map(|(output, data)| {
+            data.rows.iter().enumerate().find_map(|(row_idx, row)| {
+                row.iter()
+                    .position(|w| w == elem)
+                    .map(|col_idx| (output.clone(), row_idx, col_idx))
+            })
+        })
+    }
+
+    fn swap_positions(&mut self, output: &Output, a: (usize, usize), b: (usize, usize)) {
+        let Some(data) = self.outputs.get_mut(output) else {
+            return;
+        };
+        let (a_row, a_col) = a;
+        let (b_row, b_col) = b;
+        if a_row == b_row {
+            data.rows[a_row].swap(a_col, b_col);
+        } else {
+            let (lo_row, lo_col, hi_row, hi_col) = if a_row < b_row {
+                (a_row, a_col, b_row, b_col)
+            } else {
+                (b_row, b_col, a_row, a_col)
+            };
+            let (left, right) = data.rows.split_at_mut(hi_row);
+            std::mem::swap(&mut left[lo_row][lo_col], &mut right[0][hi_col]);
+        }
+    }
+
+    /// Swaps `window` with its neighbor in `direction`, if one exists.
+    /// Returns whether a swap occurred.
+    pub fn swap_window(&mut self, window: &CosmicMapped, direction: TilingDirection) -> bool {
+        let Some((output, row_idx, col_idx)) = self.position_of(window) else {
+            return false;
+        };
+        let Some(data) = self.outputs.get(&output) else {
+            return false;
+        };
+        let target = match direction {
+            TilingDirection::Left if col_idx > 0 => Some((row_idx, col_idx - 1)),
+            TilingDirection::Left => None,
+            TilingDirection::Right => Some((row_idx, col_idx + 1)),
+            TilingDirection::Up if row_idx > 0 => Some((row_idx - 1, col_idx)),
+            TilingDirection::Up => None,
+            TilingDirection::Down => Some((row_idx + 1, col_idx)),
+        };
+        let Some((target_row, target_col)) = target else {
+            return false;
+        };
+        match data.rows.get(target_row) {
+            Some(row) if target_col < row.len() => {}
+            _ => return false,
+        }
+        self.swap_positions(&output, (row_idx, col_idx), (target_row, target_col));
+        true
+    }
+
+    /// Swaps the grid positions of `from` and `to`. Used by the tiling move
+    /// grab to drop a dragged window onto another tile.
+    pub fn move_element(&mut self, from: &CosmicMapped, to: &CosmicMapped) {
+        let Some((from_output, from_row, from_col)) = self.position_of(from) else {
+            return;
+        };
+        let Some((to_output, to_row, to_col)) = self.position_of(to) else {
+            return;
+        };
+        if from_output != to_output {
+            return;
+        }
+        self.swap_positions(&from_output, (from_row, from_col), (to_row, to_col));
+    }
+
+    pub fn render_output<R>(
+        &self,
+        output: &Output,
+    ) -> Result<Vec<TilingRenderElement<R>>, crate::shell::workspace::OutputNotMapped>
+    where
+        R: Renderer + ImportAll,
+        <R as Renderer>::TextureId: 'static,
+    {
+        if !self.outputs.contains_key(output) {
+            return Err(crate::shell::workspace::OutputNotMapped);
+        }
+
+        let output_geo = output.geometry();
+        let output_scale = output.current_scale().fractional_scale();
+        let mut render_elements = Vec::new();
+
+        for (_, mapped, loc) in self.mapped().filter(|(o, _, _)| *o == output) {
+            let bbox = Rectangle::from_loc_and_size(loc, mapped.geometry().size);
+            if !bbox.overlaps(Rectangle::from_loc_and_size(output_geo.loc, output_geo.size)) {
+                // Tiled but positioned outside the output; skip it.
+                continue;
+            }
+
+            let local_loc = loc - output_geo.loc;
+            render_elements.extend(AsRenderElements::<R>::render_elements::<TilingRenderElement<R>>(
+                mapped,
+                local_loc.to_physical_precise_round(output_scale),
+                Scale::from(output_scale),
+            ));
+        }
+
+        Ok(render_elements)
+    }
+
+    /// Intentionally unimplemented: the tiling layout has its own
+    /// interactive move grab (`TilingMoveGrab`) for reordering, but no
+    /// pointer-grab resize yet.
+    pub fn resize_request(
+        &mut self,
+        _mapped: &CosmicMapped,
+        _seat: &Seat<State>,
+        _serial: Serial,
+        _start_data: PointerGrabStartData<State>,
+        _edges: ResizeEdge,
+    ) -> Option<ResizeGrab> {
+        None
+    }
+}
+
+render_elements! {
+    pub TilingRenderElement<R> where R: ImportAll;
+    Window=smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement,
+}
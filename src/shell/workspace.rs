@@ -1,6 +1,7 @@
 use crate::{
     shell::layout::{
         floating::{FloatingLayout, MoveSurfaceGrab},
+        scrolling::ScrollingLayout,
         tiling::TilingLayout,
     },
     state::State,
@@ -17,11 +18,24 @@ use crate::{
 use indexmap::IndexSet;
 use smithay::{
     backend::renderer::{
-        element::{surface::WaylandSurfaceRenderElement, AsRenderElements},
+        element::{
+            solid::{SolidColorBuffer, SolidColorRenderElement},
+            surface::WaylandSurfaceRenderElement,
+            AsRenderElements,
+        },
         ImportAll, Renderer,
     },
     desktop::{layer_map_for_output, space::SpaceElement, Kind, LayerSurface, Window},
-    input::{pointer::GrabStartData as PointerGrabStartData, Seat},
+    input::{
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+            GrabStartData as PointerGrabStartData, MotionEvent, PointerGrab, PointerInnerHandle,
+            RelativeMotionEvent,
+        },
+        Seat, SeatHandler,
+    },
     output::Output,
     reexports::{
         wayland_protocols::xdg::shell::server::xdg_toplevel::{self, ResizeEdge},
@@ -29,27 +43,47 @@ use smithay::{
     },
     render_elements,
     utils::{IsAlive, Logical, Point, Rectangle, Scale, Serial},
-    wayland::shell::wlr_layer::Layer,
+    wayland::shell::wlr_layer::{Anchor, ExclusiveZone, Layer},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::{
     element::CosmicMapped,
     focus::{FocusStack, FocusStackMut},
     grabs::ResizeGrab,
-    layout::{floating::FloatingRenderElement, tiling::TilingRenderElement},
+    layout::{
+        floating::FloatingRenderElement, scrolling::ScrollingRenderElement,
+        tiling::TilingRenderElement,
+    },
 };
 
 #[derive(Debug)]
 pub struct Workspace {
     pub tiling_layer: TilingLayout,
     pub floating_layer: FloatingLayout,
+    pub scrolling_layer: ScrollingLayout,
     pub tiling_enabled: bool,
+    pub scrolling_enabled: bool,
     pub fullscreen: HashMap<Output, Window>,
     pub handle: WorkspaceHandle,
     pub focus_stack: FocusStacks,
     pub pending_buffers: Vec<(ScreencopySession, BufferParams)>,
     pub screencopy_sessions: Vec<DropableSession>,
+    /// Drop-target highlight for an in-progress tiling move grab, in
+    /// global coordinates (as returned by `element_geometry`). Cleared once
+    /// the grab ends.
+    pub tiling_drop_target: Option<Rectangle<i32, Logical>>,
+    surface_outputs: HashMap<WlSurface, HashSet<Output>>,
+}
+
+/// A cardinal direction used to move focus or swap windows within the
+/// tiling and scrolling layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TilingDirection {
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
 #[derive(Debug, Default)]
@@ -59,6 +93,7 @@ pub struct FocusStacks(HashMap<Seat<State>, IndexSet<CosmicMapped>>);
 pub enum ManagedState {
     Tiling,
     Floating,
+    Scrolling,
 }
 
 impl Workspace {
@@ -66,19 +101,166 @@ impl Workspace {
         Workspace {
             tiling_layer: TilingLayout::new(),
             floating_layer: FloatingLayout::new(),
+            scrolling_layer: ScrollingLayout::new(),
             tiling_enabled: true,
+            scrolling_enabled: false,
             fullscreen: HashMap::new(),
             handle,
             focus_stack: FocusStacks::default(),
             pending_buffers: Vec::new(),
             screencopy_sessions: Vec::new(),
+            tiling_drop_target: None,
+            surface_outputs: HashMap::new(),
+        }
+    }
+
+    /// Swaps the currently focused tiled window with its neighbor in the
+    /// given direction, reordering windows in the `TilingLayout` grid and keeping
+    /// the seat's `FocusStack` pointed at the moved window.
+    pub fn swap_focused_tiled(&mut self, seat: &Seat<State>, direction: TilingDirection) {
+        if let Some(window) = self.focus_stack.get(seat).iter().next().cloned() {
+            if self.tiling_layer.mapped().any(|(_, m, _)| m == &window)
+                && self.tiling_layer.swap_window(&window, direction)
+            {
+                self.focus_stack.get_mut(seat).append(&window);
+            }
+        }
+    }
+
+    /// Moves focus within the scrolling layout: left/right scrolls the
+    /// viewport to the neighboring column, up/down moves between windows
+    /// stacked in the active column. Updates the seat's `FocusStack` to
+    /// match whatever ends up focused.
+    pub fn scrolling_move_focus(&mut self, seat: &Seat<State>, direction: TilingDirection) {
+        let focused = match direction {
+            TilingDirection::Left => self.scrolling_layer.move_focus_left(seat),
+            TilingDirection::Right => self.scrolling_layer.move_focus_right(seat),
+            TilingDirection::Up => self.scrolling_layer.move_focus_up(seat),
+            TilingDirection::Down => self.scrolling_layer.move_focus_down(seat),
+        };
+        if let Some(window) = focused {
+            self.focus_stack.get_mut(seat).append(&window);
         }
     }
 
+    /// Grows or shrinks the active scrolling column's width, e.g. from a
+    /// dedicated resize keybinding — the scrolling layout has no
+    /// pointer-grab resize of its own (see
+    /// `ScrollingLayout::resize_request`).
+    pub fn resize_scrolling_column(&mut self, seat: &Seat<State>, delta: i32) {
+        self.scrolling_layer.resize_active_column_width(seat, delta);
+    }
+
+    /// Grows or shrinks the focused scrolling window's share of its
+    /// column's height.
+    pub fn resize_scrolling_window(&mut self, seat: &Seat<State>, delta: f64) {
+        self.scrolling_layer.resize_active_window_weight(seat, delta);
+    }
+
     pub fn refresh(&mut self) {
         self.fullscreen.retain(|_, w| w.alive());
+        for output in self.floating_layer.space.outputs().cloned().collect::<Vec<_>>() {
+            self.update_working_area(&output);
+        }
         self.floating_layer.refresh();
         self.tiling_layer.refresh();
+        self.scrolling_layer.refresh();
+        self.update_surface_outputs();
+    }
+
+    /// Sends `wl_surface.enter`/`leave` to every mapped surface based on
+    /// whether its geometry currently overlaps each mapped output, so
+    /// clients can pick the right buffer scale and skip rendering when
+    /// fully off-screen. Mirrors the bookkeeping `layer_map_for_output`
+    /// already does for layer-shell surfaces.
+    fn update_surface_outputs(&mut self) {
+        let outputs = self.floating_layer.space.outputs().cloned().collect::<Vec<_>>();
+        if outputs.is_empty() {
+            return;
+        }
+
+        for mapped in self.mapped().cloned().collect::<Vec<_>>() {
+            // Fullscreened/maximized windows stay mapped at their old
+            // floating/tiling/scrolling slot while fullscreen, and are
+            // synced separately below using their fullscreen output
+            // geometry instead; skip them here so each surface is synced
+            // exactly once per refresh.
+            if mapped.windows().any(|(w, _)| self.fullscreen.values().any(|fw| fw == &w)) {
+                continue;
+            }
+            if let Some(geo) = self.element_geometry(&mapped) {
+                for (window, offset) in mapped.windows() {
+                    let window_geo = Rectangle::from_loc_and_size(geo.loc + offset, window.geometry().size);
+                    Self::sync_window_outputs(&mut self.surface_outputs, &window, window_geo, &outputs);
+                }
+            }
+        }
+
+        for (output, window) in &self.fullscreen {
+            let geo = Rectangle::from_loc_and_size(output.geometry().loc, window.geometry().size);
+            Self::sync_window_outputs(&mut self.surface_outputs, window, geo, &outputs);
+        }
+
+        for output in &outputs {
+            let layer_map = layer_map_for_output(output);
+            for layer in layer_map.layers() {
+                if let Some(layer_geo) = layer_map.layer_geometry(layer) {
+                    let geo =
+                        Rectangle::from_loc_and_size(output.geometry().loc + layer_geo.loc, layer_geo.size);
+                    Self::sync_layer_outputs(&mut self.surface_outputs, layer, geo, &outputs);
+                }
+            }
+        }
+
+        self.surface_outputs.retain(|surface, _| surface.is_alive());
+    }
+
+    fn sync_window_outputs(
+        surface_outputs: &mut HashMap<WlSurface, HashSet<Output>>,
+        window: &Window,
+        geo: Rectangle<i32, Logical>,
+        outputs: &[Output],
+    ) {
+        let surface = window.toplevel().wl_surface().clone();
+        let entered = surface_outputs.entry(surface).or_default();
+        for output in outputs {
+            match geo.intersection(output.geometry()) {
+                Some(overlap) if !overlap.is_empty() => {
+                    if entered.insert(output.clone()) {
+                        window.output_enter(output, overlap);
+                    }
+                }
+                _ => {
+                    if entered.remove(output) {
+                        window.output_leave(output);
+                    }
+                }
+            }
+        }
+    }
+
+    fn sync_layer_outputs(
+        surface_outputs: &mut HashMap<WlSurface, HashSet<Output>>,
+        layer: &LayerSurface,
+        geo: Rectangle<i32, Logical>,
+        outputs: &[Output],
+    ) {
+        let surface = layer.wl_surface().clone();
+        let entered = surface_outputs.entry(surface).or_default();
+        for output in outputs {
+            match geo.intersection(output.geometry()) {
+                Some(overlap) if !overlap.is_empty() => {
+                    if entered.insert(output.clone()) {
+                        layer.layer_surface().output_enter(output, overlap);
+                    }
+                }
+                _ => {
+                    if entered.remove(output) {
+                        layer.layer_surface().output_leave(output);
+                    }
+                }
+            }
+        }
     }
 
     pub fn commit(&mut self, surface: &WlSurface) {
@@ -95,6 +277,48 @@ impl Workspace {
     pub fn map_output(&mut self, output: &Output, position: Point<i32, Logical>) {
         self.tiling_layer.map_output(output, position);
         self.floating_layer.map_output(output, position);
+        self.scrolling_layer.map_output(output, position);
+        self.update_working_area(output);
+    }
+
+    /// Recomputes `output`'s working area (its geometry minus the exclusive
+    /// zones reserved by anchored layer-shell surfaces) and feeds it to every
+    /// layout so tiled and floating windows arrange around panels and docks
+    /// instead of underneath them. Falls back to the full output geometry
+    /// when no exclusive zones are present.
+    pub fn update_working_area(&mut self, output: &Output) {
+        let area = Self::compute_working_area(output);
+        self.floating_layer.set_working_area(output, area);
+        self.tiling_layer.set_working_area(output, area);
+        self.scrolling_layer.set_working_area(output, area);
+    }
+
+    fn compute_working_area(output: &Output) -> Rectangle<i32, Logical> {
+        let output_geo = Rectangle::from_loc_and_size((0, 0), output.geometry().size);
+        let layer_map = layer_map_for_output(output);
+
+        layer_map.layers().fold(output_geo, |mut area, layer| {
+            let state = layer.cached_state();
+            let exclusive = match state.exclusive_zone {
+                ExclusiveZone::Exclusive(size) if size > 0 => size,
+                _ => return area,
+            };
+            let anchor = state.anchor;
+
+            if anchor.contains(Anchor::LEFT) && !anchor.contains(Anchor::RIGHT) {
+                area.loc.x += exclusive;
+                area.size.w -= exclusive;
+            } else if anchor.contains(Anchor::RIGHT) && !anchor.contains(Anchor::LEFT) {
+                area.size.w -= exclusive;
+            } else if anchor.contains(Anchor::TOP) && !anchor.contains(Anchor::BOTTOM) {
+                area.loc.y += exclusive;
+                area.size.h -= exclusive;
+            } else if anchor.contains(Anchor::BOTTOM) && !anchor.contains(Anchor::TOP) {
+                area.size.h -= exclusive;
+            }
+
+            area
+        })
     }
 
     pub fn unmap_output(&mut self, output: &Output) {
@@ -103,14 +327,16 @@ impl Workspace {
         }
         self.tiling_layer.unmap_output(output);
         self.floating_layer.unmap_output(output);
+        self.scrolling_layer.unmap_output(output);
         self.refresh();
     }
 
     pub fn unmap(&mut self, mapped: &CosmicMapped) -> Option<ManagedState> {
         let was_floating = self.floating_layer.unmap(&mapped);
         let was_tiling = self.tiling_layer.unmap(&mapped).is_some();
-        if was_floating || was_tiling {
-            assert!(was_floating != was_tiling);
+        let was_scrolling = self.scrolling_layer.unmap(&mapped);
+        if was_floating || was_tiling || was_scrolling {
+            assert!((was_floating as u8 + was_tiling as u8 + was_scrolling as u8) == 1);
         }
         self.focus_stack
             .0
@@ -120,6 +346,8 @@ impl Workspace {
             Some(ManagedState::Floating)
         } else if was_tiling {
             Some(ManagedState::Tiling)
+        } else if was_scrolling {
+            Some(ManagedState::Scrolling)
         } else {
             None
         }
@@ -129,6 +357,7 @@ impl Workspace {
         self.floating_layer
             .mapped()
             .chain(self.tiling_layer.mapped().map(|(_, w, _)| w))
+            .chain(self.scrolling_layer.mapped().map(|(_, w, _)| w))
             .find(|e| {
                 e.windows()
                     .any(|(w, _)| w.toplevel().wl_surface() == surface)
@@ -141,6 +370,7 @@ impl Workspace {
             .outputs_for_element(elem)
             .into_iter()
             .chain(self.tiling_layer.output_for_element(elem).cloned())
+            .chain(self.scrolling_layer.output_for_element(elem).cloned())
     }
 
     pub fn output_under(&self, point: Point<i32, Logical>) -> Option<&Output> {
@@ -169,6 +399,21 @@ impl Workspace {
             })
     }
 
+    /// Like `element_under`, but only considers windows in the tiling
+    /// layer. Used to resolve tiling move-grab drop targets, which must
+    /// never resolve to a floating window that happens to be on top.
+    pub fn tiled_element_under(
+        &self,
+        location: Point<f64, Logical>,
+    ) -> Option<(&CosmicMapped, Point<i32, Logical>)> {
+        self.tiling_layer.mapped().find_map(|(_, mapped, loc)| {
+            let test_point = location - loc.to_f64() + mapped.geometry().loc.to_f64();
+            mapped
+                .is_in_input_region(&test_point)
+                .then_some((mapped, loc - mapped.geometry().loc))
+        })
+    }
+
     pub fn element_geometry(&self, elem: &CosmicMapped) -> Option<Rectangle<i32, Logical>> {
         let space = &self.floating_layer.space;
         let outputs = space.outputs().collect::<Vec<_>>();
@@ -184,6 +429,7 @@ impl Workspace {
             .space
             .element_geometry(elem)
             .or_else(|| self.tiling_layer.element_geometry(elem))
+            .or_else(|| self.scrolling_layer.element_geometry(elem))
             .map(|mut geo| {
                 geo.loc += offset;
                 geo
@@ -307,6 +553,10 @@ impl Workspace {
             self.tiling_layer
                 .resize_request(mapped, seat, serial, start_data, edges)
                 .map(Into::into)
+        } else if self.scrolling_layer.mapped().any(|(_, m, _)| m == mapped) {
+            self.scrolling_layer
+                .resize_request(mapped, seat, serial, start_data, edges)
+                .map(Into::into)
         } else {
             None
         }
@@ -319,7 +569,7 @@ impl Workspace {
         output: &Output,
         _serial: Serial,
         start_data: PointerGrabStartData<State>,
-    ) -> Option<MoveSurfaceGrab> {
+    ) -> Option<MoveGrab> {
         let pointer = seat.get_pointer().unwrap();
         let pos = pointer.current_location();
 
@@ -344,20 +594,44 @@ impl Workspace {
         }
 
         let was_floating = self.floating_layer.unmap(&mapped);
-        //let was_tiled = self.tiling_layer.unmap(&mapped);
-        //assert!(was_floating != was_tiled);
+        let was_tiled = !was_floating && self.tiling_layer.mapped().any(|(_, m, _)| m == &mapped);
+        let was_scrolling = !was_floating && !was_tiled && self.scrolling_layer.unmap(&mapped);
 
         if was_floating {
-            Some(MoveSurfaceGrab::new(
+            Some(MoveGrab::Floating(MoveSurfaceGrab::new(
                 start_data,
                 mapped,
                 seat,
                 pos,
                 initial_window_location,
                 output.geometry().loc,
-            ))
+            )))
+        } else if was_tiled {
+            // Tiled windows stay in the tiling grid during the drag; the
+            // grab highlights the candidate drop slot and reorders the
+            // grid on release instead of falling back to floating.
+            Some(MoveGrab::Tiling(TilingMoveGrab::new(
+                start_data,
+                mapped,
+                seat,
+                output.clone(),
+                pos,
+            )))
+        } else if was_scrolling {
+            // The scrolling layout has no interactive reorder grab of its
+            // own yet, so dragging a column window detaches it into
+            // floating for the duration of the move, same as toggling it
+            // floating by hand.
+            Some(MoveGrab::Floating(MoveSurfaceGrab::new(
+                start_data,
+                mapped,
+                seat,
+                pos,
+                initial_window_location,
+                output.geometry().loc,
+            )))
         } else {
-            None // TODO
+            None
         }
     }
 
@@ -374,7 +648,7 @@ impl Workspace {
                 self.floating_layer.map(window, seat, None);
             }
             self.tiling_enabled = false;
-        } else {
+        } else if !self.scrolling_enabled {
             let focus_stack = self.focus_stack.get(seat);
             for window in self
                 .floating_layer
@@ -390,6 +664,34 @@ impl Workspace {
         }
     }
 
+    pub fn toggle_scrolling(&mut self, seat: &Seat<State>) {
+        if self.scrolling_enabled {
+            for window in self
+                .scrolling_layer
+                .mapped()
+                .map(|(_, m, _)| m.clone())
+                .collect::<Vec<_>>()
+                .into_iter()
+            {
+                self.scrolling_layer.unmap(&window);
+                self.floating_layer.map(window, seat, None);
+            }
+            self.scrolling_enabled = false;
+        } else if !self.tiling_enabled {
+            for window in self
+                .floating_layer
+                .mapped()
+                .cloned()
+                .collect::<Vec<_>>()
+                .into_iter()
+            {
+                self.floating_layer.unmap(&window);
+                self.scrolling_layer.map(window, seat);
+            }
+            self.scrolling_enabled = true;
+        }
+    }
+
     pub fn toggle_floating_window(&mut self, seat: &Seat<State>) {
         if self.tiling_enabled {
             if let Some(window) = self.focus_stack.get(seat).iter().next().cloned() {
@@ -402,6 +704,16 @@ impl Workspace {
                     self.tiling_layer.map(window, seat, focus_stack.iter())
                 }
             }
+        } else if self.scrolling_enabled {
+            if let Some(window) = self.focus_stack.get(seat).iter().next().cloned() {
+                if self.scrolling_layer.mapped().any(|(_, m, _)| m == &window) {
+                    self.scrolling_layer.unmap(&window);
+                    self.floating_layer.map(window, seat, None);
+                } else if self.floating_layer.mapped().any(|w| w == &window) {
+                    self.floating_layer.unmap(&window);
+                    self.scrolling_layer.map(window, seat);
+                }
+            }
         }
     }
 
@@ -409,12 +721,14 @@ impl Workspace {
         self.floating_layer
             .mapped()
             .chain(self.tiling_layer.mapped().map(|(_, w, _)| w))
+            .chain(self.scrolling_layer.mapped().map(|(_, w, _)| w))
     }
 
     pub fn windows(&self) -> impl Iterator<Item = Window> + '_ {
         self.floating_layer
             .windows()
             .chain(self.tiling_layer.windows().map(|(_, w, _)| w))
+            .chain(self.scrolling_layer.windows().map(|(_, w, _)| w))
     }
 
     pub fn render_output<R>(
@@ -428,7 +742,11 @@ impl Workspace {
         let mut render_elements = Vec::new();
 
         let output_scale = output.current_scale().fractional_scale();
+        let output_geo = Rectangle::from_loc_and_size(output.geometry().loc, output.geometry().size);
         let layer_map = layer_map_for_output(output);
+        let layer_overlaps_output = |geo: Rectangle<i32, Logical>| -> bool {
+            Rectangle::from_loc_and_size(output_geo.loc + geo.loc, geo.size).overlaps(output_geo)
+        };
 
         if let Some(fullscreen) = self.fullscreen.get(output) {
             // overlay layer surfaces
@@ -440,8 +758,10 @@ impl Workspace {
                     .filter_map(|surface| {
                         layer_map
                             .layer_geometry(surface)
-                            .map(|geo| (geo.loc, surface))
+                            .map(|geo| (geo, surface))
                     })
+                    .filter(|(geo, _)| layer_overlaps_output(*geo))
+                    .map(|(geo, surface)| (geo.loc, surface))
                     .flat_map(|(loc, surface)| {
                         AsRenderElements::<R>::render_elements::<WorkspaceRenderElement<R>>(
                             surface,
@@ -456,10 +776,6 @@ impl Workspace {
                 WorkspaceRenderElement<R>,
             >(fullscreen, (0, 0).into(), output_scale.into()));
         } else {
-            // TODO: Handle modes like
-            // - keyboard window swapping
-            // - resizing / moving in tiling
-
             // overlay and top layer surfaces
             let lower = {
                 let (lower, upper): (Vec<&LayerSurface>, Vec<&LayerSurface>) = layer_map
@@ -473,8 +789,10 @@ impl Workspace {
                         .filter_map(|surface| {
                             layer_map
                                 .layer_geometry(surface)
-                                .map(|geo| (geo.loc, surface))
+                                .map(|geo| (geo, surface))
                         })
+                        .filter(|(geo, _)| layer_overlaps_output(*geo))
+                        .map(|(geo, surface)| (geo.loc, surface))
                         .flat_map(|(loc, surface)| {
                             AsRenderElements::<R>::render_elements::<WorkspaceRenderElement<R>>(
                                 surface,
@@ -487,7 +805,9 @@ impl Workspace {
                 lower
             };
 
-            // floating surfaces
+            // floating surfaces (floating_layer wraps a smithay Space,
+            // which already culls elements that don't overlap `output`
+            // internally)
             render_elements.extend(
                 self.floating_layer
                     .render_output::<R>(output)?
@@ -495,13 +815,46 @@ impl Workspace {
                     .map(WorkspaceRenderElement::from),
             );
 
-            //tiling surfaces
-            render_elements.extend(
-                self.tiling_layer
-                    .render_output::<R>(output)?
-                    .into_iter()
-                    .map(WorkspaceRenderElement::from),
-            );
+            // tiling surfaces; skip the pass if nothing is tiled on this
+            // output at all (scans the tiling layer's mapped windows until
+            // one on this output is found), render_output itself culls
+            // per-window by bbox
+            if self.tiling_layer.mapped().any(|(o, _, _)| o == output) {
+                render_elements.extend(
+                    self.tiling_layer
+                        .render_output::<R>(output)?
+                        .into_iter()
+                        .map(WorkspaceRenderElement::from),
+                );
+            }
+
+            // scrolling-tiling surfaces (render_output already culls columns
+            // scrolled outside the output, but skip the pass entirely if
+            // this output has nothing mapped into the scrolling layer)
+            if self.scrolling_layer.mapped().any(|(o, _, _)| o == output) {
+                render_elements.extend(
+                    self.scrolling_layer
+                        .render_output::<R>(output)?
+                        .into_iter()
+                        .map(WorkspaceRenderElement::from),
+                );
+            }
+
+            // drop-target highlight for an in-progress tiling move grab.
+            // `tiling_drop_target` comes from `element_geometry`, which is
+            // in global space, but every other element here is rendered in
+            // output-local coordinates, so subtract the output's offset.
+            if let Some(target) = self.tiling_drop_target {
+                let local_loc = target.loc - output_geo.loc;
+                let buffer = SolidColorBuffer::new(target.size, [0.4, 0.6, 1.0, 0.4]);
+                render_elements.push(WorkspaceRenderElement::from(SolidColorRenderElement::from_buffer(
+                    &buffer,
+                    local_loc.to_physical_precise_round(output_scale),
+                    output_scale.into(),
+                    1.0,
+                    smithay::backend::renderer::element::Kind::Unspecified,
+                )));
+            }
 
             // bottom and background layer surfaces
             {
@@ -511,8 +864,10 @@ impl Workspace {
                         .filter_map(|surface| {
                             layer_map
                                 .layer_geometry(surface)
-                                .map(|geo| (geo.loc, surface))
+                                .map(|geo| (geo, surface))
                         })
+                        .filter(|(geo, _)| layer_overlaps_output(*geo))
+                        .map(|(geo, surface)| (geo.loc, surface))
                         .flat_map(|(loc, surface)| {
                             AsRenderElements::<R>::render_elements::<WorkspaceRenderElement<R>>(
                                 surface,
@@ -540,9 +895,358 @@ impl FocusStacks {
 
 pub struct OutputNotMapped;
 
+/// The grab driving an interactive window move, dispatched to the
+/// originating layout's own grab implementation.
+pub enum MoveGrab {
+    Floating(MoveSurfaceGrab),
+    Tiling(TilingMoveGrab),
+}
+
+impl PointerGrab<State> for MoveGrab {
+    fn motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.motion(data, handle, focus, event),
+            MoveGrab::Tiling(grab) => grab.motion(data, handle, focus, event),
+        }
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.relative_motion(data, handle, focus, event),
+            MoveGrab::Tiling(grab) => grab.relative_motion(data, handle, focus, event),
+        }
+    }
+
+    fn button(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>, event: &ButtonEvent) {
+        match self {
+            MoveGrab::Floating(grab) => grab.button(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.button(data, handle, event),
+        }
+    }
+
+    fn axis(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>, details: AxisFrame) {
+        match self {
+            MoveGrab::Floating(grab) => grab.axis(data, handle, details),
+            MoveGrab::Tiling(grab) => grab.axis(data, handle, details),
+        }
+    }
+
+    fn frame(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>) {
+        match self {
+            MoveGrab::Floating(grab) => grab.frame(data, handle),
+            MoveGrab::Tiling(grab) => grab.frame(data, handle),
+        }
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_swipe_begin(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_swipe_begin(data, handle, event),
+        }
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_swipe_update(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_swipe_update(data, handle, event),
+        }
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_swipe_end(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_swipe_end(data, handle, event),
+        }
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_pinch_begin(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_pinch_begin(data, handle, event),
+        }
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_pinch_update(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_pinch_update(data, handle, event),
+        }
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchEndEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_pinch_end(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_pinch_end(data, handle, event),
+        }
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_hold_begin(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_hold_begin(data, handle, event),
+        }
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldEndEvent,
+    ) {
+        match self {
+            MoveGrab::Floating(grab) => grab.gesture_hold_end(data, handle, event),
+            MoveGrab::Tiling(grab) => grab.gesture_hold_end(data, handle, event),
+        }
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<State> {
+        match self {
+            MoveGrab::Floating(grab) => grab.start_data(),
+            MoveGrab::Tiling(grab) => grab.start_data(),
+        }
+    }
+
+    fn unset(&mut self, data: &mut State) {
+        match self {
+            MoveGrab::Floating(grab) => grab.unset(data),
+            MoveGrab::Tiling(grab) => grab.unset(data),
+        }
+    }
+}
+
+impl From<MoveSurfaceGrab> for MoveGrab {
+    fn from(grab: MoveSurfaceGrab) -> Self {
+        MoveGrab::Floating(grab)
+    }
+}
+
+impl From<TilingMoveGrab> for MoveGrab {
+    fn from(grab: TilingMoveGrab) -> Self {
+        MoveGrab::Tiling(grab)
+    }
+}
+
+/// An interactive move of a tiled window. Unlike the floating move grab,
+/// the dragged window stays part of the tiling grid for the duration of
+/// the drag: motion just tracks which mapped window the cursor is over and
+/// exposes it as `Workspace::tiling_drop_target` for `render_output` to
+/// highlight, and release reorders the grid instead of detaching into
+/// floating.
+pub struct TilingMoveGrab {
+    start_data: PointerGrabStartData<State>,
+    mapped: CosmicMapped,
+    seat: Seat<State>,
+    output: Output,
+    last_location: Point<f64, Logical>,
+}
+
+impl TilingMoveGrab {
+    pub fn new(
+        start_data: PointerGrabStartData<State>,
+        mapped: CosmicMapped,
+        seat: &Seat<State>,
+        output: Output,
+        location: Point<f64, Logical>,
+    ) -> TilingMoveGrab {
+        TilingMoveGrab {
+            start_data,
+            mapped,
+            seat: seat.clone(),
+            output,
+            last_location: location,
+        }
+    }
+}
+
+impl PointerGrab<State> for TilingMoveGrab {
+    fn motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(<State as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        handle.motion(data, None, event);
+        self.last_location = event.location;
+
+        if let Some(workspace) = data.common.shell.space_for_output_mut(&self.output) {
+            workspace.tiling_drop_target = workspace
+                .tiled_element_under(self.last_location)
+                .filter(|(mapped, _)| *mapped != &self.mapped)
+                .and_then(|(mapped, _)| workspace.element_geometry(mapped));
+        }
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        focus: Option<(<State as SeatHandler>::PointerFocus, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>, event: &ButtonEvent) {
+        handle.button(data, event);
+        if handle.current_pressed().is_empty() {
+            if let Some(workspace) = data.common.shell.space_for_output_mut(&self.output) {
+                let target = workspace
+                    .tiled_element_under(self.last_location)
+                    .filter(|(mapped, _)| *mapped != &self.mapped)
+                    .map(|(mapped, _)| mapped.clone());
+                if let Some(target) = target {
+                    workspace.tiling_layer.move_element(&self.mapped, &target);
+                }
+                workspace.tiling_drop_target = None;
+            }
+            handle.unset_grab(data, event.serial, event.time);
+        }
+    }
+
+    fn axis(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>, details: AxisFrame) {
+        handle.axis(data, details)
+    }
+
+    fn frame(&mut self, data: &mut State, handle: &mut PointerInnerHandle<'_, State>) {
+        handle.frame(data)
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event)
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event)
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event)
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event)
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event)
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event)
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event)
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event)
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<State> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut State) {
+        if let Some(workspace) = data.common.shell.space_for_output_mut(&self.output) {
+            workspace.tiling_drop_target = None;
+        }
+    }
+}
+
 render_elements! {
     pub WorkspaceRenderElement<R> where R: ImportAll;
     Wayland=WaylandSurfaceRenderElement,
     Floating=FloatingRenderElement<R>,
     Tiling=TilingRenderElement<R>,
+    Scrolling=ScrollingRenderElement<R>,
+    TilingDropTarget=SolidColorRenderElement,
 }